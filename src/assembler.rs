@@ -0,0 +1,413 @@
+use std::collections::HashMap;
+
+use crate::{Opcode, Register, Trap};
+
+/// Assemble LC-3 assembly text into the `.obj` image consumed by
+/// [`crate::load_image`]: a big-endian origin word followed by one big-endian
+/// word per assembled instruction or datum.
+///
+/// The classic two-pass scheme is used. Pass one builds the symbol table by
+/// walking the source with a location counter seeded from `.ORIG`; pass two
+/// encodes each statement, resolving label references into PC-relative offsets.
+pub fn assemble(source: &str) -> Result<Vec<u8>, String> {
+    let statements = parse(source)?;
+    let (origin, symbols) = first_pass(&statements)?;
+    second_pass(origin, &statements, &symbols)
+}
+
+/// A single meaningful source line: an optional label plus an optional
+/// directive or instruction with its operands.
+struct Statement {
+    line: usize,
+    label: Option<String>,
+    op: Option<String>,
+    operands: Vec<String>,
+}
+
+/// Split the source into statements, stripping comments and blank lines and
+/// separating a leading label from the mnemonic that follows it.
+fn parse(source: &str) -> Result<Vec<Statement>, String> {
+    let mut statements = Vec::new();
+
+    for (idx, raw) in source.lines().enumerate() {
+        let line = idx + 1;
+        let code = raw.split(';').next().unwrap_or("");
+        if code.trim().is_empty() {
+            continue;
+        }
+
+        // `.STRINGZ` carries a quoted operand that may contain spaces and
+        // commas, so the quoted text is pulled out verbatim before the rest of
+        // the line is tokenized on whitespace and commas.
+        let upper = code.to_uppercase();
+        let mut tokens = Vec::new();
+        let mut string_literal = None;
+        if let Some(start) = upper.find(".STRINGZ") {
+            let head = &code[..start];
+            tokens.extend(split_tokens(head));
+            tokens.push(".STRINGZ".to_string());
+            let rest = &code[start + ".STRINGZ".len()..];
+            string_literal = Some(parse_string_literal(rest, line)?);
+        } else {
+            tokens.extend(split_tokens(code));
+        }
+
+        let mut iter = tokens.into_iter().peekable();
+        let mut label = None;
+        if let Some(first) = iter.peek() {
+            if !is_mnemonic(first) {
+                label = Some(first.clone());
+                iter.next();
+            }
+        }
+
+        let op = iter.next();
+        let mut operands: Vec<String> = iter.collect();
+        if let Some(text) = string_literal {
+            operands.push(text);
+        }
+
+        statements.push(Statement { line, label, op, operands });
+    }
+
+    Ok(statements)
+}
+
+fn split_tokens(text: &str) -> Vec<String> {
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Decode the escaped, double-quoted operand of a `.STRINGZ` directive.
+fn parse_string_literal(rest: &str, line: usize) -> Result<String, String> {
+    let bytes: Vec<char> = rest.chars().collect();
+    let start = bytes
+        .iter()
+        .position(|&c| c == '"')
+        .ok_or_else(|| format!("line {}: .STRINGZ expects a quoted string", line))?;
+
+    let mut out = String::new();
+    let mut i = start + 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            '"' => return Ok(out),
+            '\\' if i + 1 < bytes.len() => {
+                i += 1;
+                out.push(match bytes[i] {
+                    'n' => '\n',
+                    't' => '\t',
+                    '0' => '\0',
+                    '\\' => '\\',
+                    '"' => '"',
+                    other => other,
+                });
+            }
+            c => out.push(c),
+        }
+        i += 1;
+    }
+
+    Err(format!("line {}: unterminated string literal", line))
+}
+
+/// Is `token` a known directive, instruction, or trap alias (rather than a
+/// label definition)?
+fn is_mnemonic(token: &str) -> bool {
+    let t = token.to_uppercase();
+    if t.starts_with('.') {
+        return true;
+    }
+    if let Some(suffix) = t.strip_prefix("BR") {
+        return suffix.chars().all(|c| matches!(c, 'N' | 'Z' | 'P'));
+    }
+    matches!(
+        t.as_str(),
+        "ADD" | "AND" | "NOT" | "JMP" | "RET" | "JSR" | "JSRR" | "LD" | "LDI" | "LDR"
+            | "LEA" | "ST" | "STI" | "STR" | "TRAP" | "RTI" | "GETC" | "OUT" | "PUTS"
+            | "IN" | "PUTSP" | "HALT"
+    )
+}
+
+/// Number of words a statement occupies in the loaded image.
+fn statement_size(stmt: &Statement) -> Result<u16, String> {
+    let op = match &stmt.op {
+        Some(op) => op.to_uppercase(),
+        None => return Ok(0),
+    };
+
+    match op.as_str() {
+        ".ORIG" | ".END" => Ok(0),
+        ".FILL" => Ok(1),
+        ".BLKW" => {
+            let n = stmt
+                .operands
+                .first()
+                .ok_or_else(|| format!("line {}: .BLKW expects a count", stmt.line))?;
+            Ok(parse_number(n).ok_or_else(|| {
+                format!("line {}: invalid .BLKW count `{}`", stmt.line, n)
+            })? as u16)
+        }
+        ".STRINGZ" => {
+            let text = stmt
+                .operands
+                .last()
+                .ok_or_else(|| format!("line {}: .STRINGZ expects a string", stmt.line))?;
+            Ok(text.chars().count() as u16 + 1)
+        }
+        _ => Ok(1),
+    }
+}
+
+/// Pass one: seed the location counter from `.ORIG` and record each label's
+/// address, returning the origin and the completed symbol table.
+fn first_pass(statements: &[Statement]) -> Result<(u16, HashMap<String, u16>), String> {
+    let mut symbols = HashMap::new();
+    let mut origin = None;
+    let mut lc = 0u16;
+
+    for stmt in statements {
+        let is_orig = stmt.op.as_deref().map(str::to_uppercase).as_deref() == Some(".ORIG");
+        if origin.is_none() && !is_orig {
+            return Err(format!("line {}: program must begin with .ORIG", stmt.line));
+        }
+
+        if is_orig {
+            let addr = stmt
+                .operands
+                .first()
+                .and_then(|o| parse_number(o))
+                .ok_or_else(|| format!("line {}: .ORIG expects an address", stmt.line))?;
+            origin = Some(addr as u16);
+            lc = addr as u16;
+        }
+
+        if let Some(label) = &stmt.label {
+            if symbols.insert(label.clone(), lc).is_some() {
+                return Err(format!("line {}: duplicate label `{}`", stmt.line, label));
+            }
+        }
+
+        if stmt.op.as_deref().map(str::to_uppercase).as_deref() == Some(".END") {
+            break;
+        }
+
+        lc = lc.wrapping_add(statement_size(stmt)?);
+    }
+
+    let origin = origin.ok_or_else(|| "missing .ORIG directive".to_string())?;
+    Ok((origin, symbols))
+}
+
+/// Pass two: encode each statement into words and serialize the image.
+fn second_pass(
+    origin: u16,
+    statements: &[Statement],
+    symbols: &HashMap<String, u16>,
+) -> Result<Vec<u8>, String> {
+    let mut image = vec![origin];
+    let mut lc = origin;
+
+    for stmt in statements {
+        let op = match &stmt.op {
+            Some(op) => op.to_uppercase(),
+            None => continue,
+        };
+
+        match op.as_str() {
+            ".ORIG" => continue,
+            ".END" => break,
+            ".FILL" => {
+                let operand = stmt
+                    .operands
+                    .first()
+                    .ok_or_else(|| format!("line {}: .FILL expects a value", stmt.line))?;
+                let value = resolve_value(operand, symbols)
+                    .ok_or_else(|| format!("line {}: invalid .FILL `{}`", stmt.line, operand))?;
+                image.push(value);
+                lc = lc.wrapping_add(1);
+            }
+            ".BLKW" => {
+                let n = statement_size(stmt)?;
+                image.resize(image.len() + n as usize, 0);
+                lc = lc.wrapping_add(n);
+            }
+            ".STRINGZ" => {
+                let text = stmt.operands.last().unwrap();
+                for c in text.chars() {
+                    image.push(c as u16);
+                }
+                image.push(0);
+                lc = lc.wrapping_add(text.chars().count() as u16 + 1);
+            }
+            _ => {
+                let word = encode(stmt, lc, symbols)?;
+                image.push(word);
+                lc = lc.wrapping_add(1);
+            }
+        }
+    }
+
+    let mut bytes = Vec::with_capacity(image.len() * 2);
+    for word in image {
+        bytes.extend_from_slice(&word.to_be_bytes());
+    }
+    Ok(bytes)
+}
+
+/// Encode a single instruction located at address `addr`.
+fn encode(stmt: &Statement, addr: u16, symbols: &HashMap<String, u16>) -> Result<u16, String> {
+    let op = stmt.op.as_ref().unwrap().to_uppercase();
+    let ops = &stmt.operands;
+    let err = |msg: &str| format!("line {}: {}", stmt.line, msg);
+
+    // Fetch operand `i`, reporting a line error rather than panicking when a
+    // statement is missing operands.
+    let arg = |i: usize| -> Result<&str, String> {
+        ops.get(i)
+            .map(String::as_str)
+            .ok_or_else(|| err(&format!("{} is missing operand {}", op, i + 1)))
+    };
+
+    // PC-relative offset of a label, range-checked against a `bits`-wide field.
+    let pc_offset = |operand: &str, bits: u8| -> Result<u16, String> {
+        let target = symbols
+            .get(operand)
+            .copied()
+            .ok_or_else(|| err(&format!("unknown label `{}`", operand)))?;
+        let offset = (target as i32) - (addr as i32 + 1);
+        let limit = 1i32 << (bits - 1);
+        if offset < -limit || offset >= limit {
+            return Err(err(&format!("offset to `{}` out of range", operand)));
+        }
+        Ok((offset as u16) & ((1 << bits) - 1))
+    };
+
+    // A signed immediate, range-checked against a `bits`-wide two's-complement
+    // field just as label offsets are.
+    let immediate = |token: &str, bits: u8| -> Result<u16, String> {
+        let value = parse_number(token)
+            .ok_or_else(|| err(&format!("invalid operand `{}`", token)))?;
+        let limit = 1i64 << (bits - 1);
+        if value < -limit || value >= limit {
+            return Err(err(&format!("immediate `{}` out of range", token)));
+        }
+        Ok((value as u16) & ((1 << bits) - 1))
+    };
+
+    let reg = |operand: &str| -> Result<u16, String> {
+        parse_register(operand).ok_or_else(|| err(&format!("expected register, got `{}`", operand)))
+    };
+
+    let word = match op.as_str() {
+        "ADD" | "AND" => {
+            let code = if op == "ADD" { Opcode::ADD } else { Opcode::AND } as u16;
+            let dr = reg(arg(0)?)?;
+            let sr1 = reg(arg(1)?)?;
+            let base = (code << 12) | (dr << 9) | (sr1 << 6);
+            let src2 = arg(2)?;
+            if let Some(sr2) = parse_register(src2) {
+                base | sr2
+            } else {
+                base | (1 << 5) | immediate(src2, 5)?
+            }
+        }
+        "NOT" => {
+            let dr = reg(arg(0)?)?;
+            let sr = reg(arg(1)?)?;
+            ((Opcode::NOT as u16) << 12) | (dr << 9) | (sr << 6) | 0x3F
+        }
+        _ if op.starts_with("BR") => {
+            let suffix = op.strip_prefix("BR").unwrap_or("");
+            let (mut n, mut z, mut p) = (false, false, false);
+            for c in suffix.chars() {
+                match c {
+                    'N' => n = true,
+                    'Z' => z = true,
+                    'P' => p = true,
+                    _ => {}
+                }
+            }
+            // A bare BR branches unconditionally, like BRnzp.
+            if suffix.is_empty() {
+                n = true;
+                z = true;
+                p = true;
+            }
+            let flags = ((n as u16) << 2) | ((z as u16) << 1) | (p as u16);
+            ((Opcode::BRANCH as u16) << 12) | (flags << 9) | pc_offset(arg(0)?, 9)?
+        }
+        "JMP" => ((Opcode::JUMP as u16) << 12) | (reg(arg(0)?)? << 6),
+        "RET" => ((Opcode::JUMP as u16) << 12) | ((Register::R7 as u16) << 6),
+        "JSR" => ((Opcode::JUMPR as u16) << 12) | (1 << 11) | pc_offset(arg(0)?, 11)?,
+        "JSRR" => ((Opcode::JUMPR as u16) << 12) | (reg(arg(0)?)? << 6),
+        "LD" => ((Opcode::LOAD as u16) << 12) | (reg(arg(0)?)? << 9) | pc_offset(arg(1)?, 9)?,
+        "LDI" => ((Opcode::LOADI as u16) << 12) | (reg(arg(0)?)? << 9) | pc_offset(arg(1)?, 9)?,
+        "LDR" => {
+            let dr = reg(arg(0)?)?;
+            let base = reg(arg(1)?)?;
+            ((Opcode::LOADR as u16) << 12) | (dr << 9) | (base << 6) | immediate(arg(2)?, 6)?
+        }
+        "LEA" => ((Opcode::LEA as u16) << 12) | (reg(arg(0)?)? << 9) | pc_offset(arg(1)?, 9)?,
+        "ST" => ((Opcode::STORE as u16) << 12) | (reg(arg(0)?)? << 9) | pc_offset(arg(1)?, 9)?,
+        "STI" => ((Opcode::STOREI as u16) << 12) | (reg(arg(0)?)? << 9) | pc_offset(arg(1)?, 9)?,
+        "STR" => {
+            let sr = reg(arg(0)?)?;
+            let base = reg(arg(1)?)?;
+            ((Opcode::STORER as u16) << 12) | (sr << 9) | (base << 6) | immediate(arg(2)?, 6)?
+        }
+        "RTI" => (Opcode::RTI as u16) << 12,
+        "TRAP" => {
+            let token = arg(0)?;
+            let vec = parse_number(token)
+                .ok_or_else(|| err(&format!("invalid trap vector `{}`", token)))?;
+            if !(0..=0xFF).contains(&vec) {
+                return Err(err(&format!("trap vector `{}` out of range", token)));
+            }
+            ((Opcode::TRAP as u16) << 12) | (vec as u16)
+        }
+        "GETC" => ((Opcode::TRAP as u16) << 12) | Trap::GETC as u16,
+        "OUT" => ((Opcode::TRAP as u16) << 12) | Trap::OUT as u16,
+        "PUTS" => ((Opcode::TRAP as u16) << 12) | Trap::PUTS as u16,
+        "IN" => ((Opcode::TRAP as u16) << 12) | Trap::IN as u16,
+        "PUTSP" => ((Opcode::TRAP as u16) << 12) | Trap::PUTSP as u16,
+        "HALT" => ((Opcode::TRAP as u16) << 12) | Trap::HALT as u16,
+        other => return Err(err(&format!("unknown instruction `{}`", other))),
+    };
+
+    Ok(word)
+}
+
+/// Parse a register operand such as `R3`, returning its number 0..=7.
+fn parse_register(token: &str) -> Option<u16> {
+    let t = token.to_uppercase();
+    let num = t.strip_prefix('R')?;
+    match num.parse::<u16>() {
+        Ok(n) if n < 8 => Some(n),
+        _ => None,
+    }
+}
+
+/// Parse an immediate: `#` decimal, `x`/`0x` hexadecimal, or a bare decimal.
+fn parse_number(token: &str) -> Option<i64> {
+    let t = token.trim();
+    if let Some(rest) = t.strip_prefix('#') {
+        rest.parse().ok()
+    } else if let Some(rest) = t.strip_prefix("0x").or_else(|| t.strip_prefix("0X")) {
+        i64::from_str_radix(rest, 16).ok()
+    } else if let Some(rest) = t.strip_prefix('x').or_else(|| t.strip_prefix('X')) {
+        i64::from_str_radix(rest, 16).ok()
+    } else {
+        t.parse().ok()
+    }
+}
+
+/// Resolve a `.FILL` operand, which may be a numeric literal or a label.
+fn resolve_value(token: &str, symbols: &HashMap<String, u16>) -> Option<u16> {
+    if let Some(value) = parse_number(token) {
+        Some(value as u16)
+    } else {
+        symbols.get(token).copied()
+    }
+}
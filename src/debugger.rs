@@ -0,0 +1,133 @@
+use std::collections::BTreeSet;
+use std::io::{self, BufRead, Write};
+
+use crate::decode::disassemble;
+use crate::vm::Lc3Vm;
+use crate::Register;
+
+/// Drive a VM interactively, one instruction at a time. Commands:
+///
+/// * `s` — single-step one instruction
+/// * `c` — continue until a breakpoint or halt
+/// * `b <addr>` / `d <addr>` — set or delete a breakpoint
+/// * `r` — dump the registers
+/// * `m <addr> [count]` — dump memory
+/// * `q` — quit
+pub fn debug(vm: &mut Lc3Vm) {
+    let mut breakpoints: BTreeSet<u16> = BTreeSet::new();
+    let stdin = io::stdin();
+
+    println!(
+        "lc3 debugger: (s)tep, (c)ontinue, (b)reak <addr>, (d)elete <addr>, (r)egs, (m)em <addr> [n], (q)uit"
+    );
+
+    loop {
+        print!("(x{:04X}) ", vm.register(Register::PC));
+        io::stdout().flush().ok();
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).unwrap_or(0) == 0 {
+            break; // end of input
+        }
+
+        let mut parts = line.split_whitespace();
+        let cmd = match parts.next() {
+            Some(cmd) => cmd,
+            None => continue,
+        };
+
+        match cmd {
+            "s" | "step" => single_step(vm),
+            "c" | "continue" => continue_run(vm, &breakpoints),
+            "b" | "break" => match parts.next().and_then(parse_addr) {
+                Some(addr) => {
+                    breakpoints.insert(addr);
+                    println!("breakpoint set at x{:04X}", addr);
+                }
+                None => println!("usage: b <addr>"),
+            },
+            "d" | "delete" => match parts.next().and_then(parse_addr) {
+                Some(addr) => {
+                    breakpoints.remove(&addr);
+                    println!("breakpoint cleared at x{:04X}", addr);
+                }
+                None => println!("usage: d <addr>"),
+            },
+            "r" | "regs" => print_registers(vm),
+            "m" | "mem" => match parts.next().and_then(parse_addr) {
+                Some(addr) => {
+                    let count = parts.next().and_then(|s| s.parse().ok()).unwrap_or(1);
+                    print_memory(vm, addr, count);
+                }
+                None => println!("usage: m <addr> [count]"),
+            },
+            "q" | "quit" => break,
+            other => println!("unknown command `{}`", other),
+        }
+
+        if !vm.running {
+            println!("machine halted");
+            break;
+        }
+    }
+}
+
+fn single_step(vm: &mut Lc3Vm) {
+    if let Err(fault) = vm.check_interrupts().and_then(|()| vm.step()) {
+        println!("fault: {}", fault);
+        vm.running = false;
+        return;
+    }
+    let pc = vm.register(Register::PC);
+    println!("x{:04X}  {}", pc, disassemble(vm.read(pc)));
+}
+
+fn continue_run(vm: &mut Lc3Vm, breakpoints: &BTreeSet<u16>) {
+    loop {
+        if let Err(fault) = vm.check_interrupts().and_then(|()| vm.step()) {
+            println!("fault: {}", fault);
+            vm.running = false;
+            return;
+        }
+        if !vm.running {
+            return;
+        }
+        let pc = vm.register(Register::PC);
+        if breakpoints.contains(&pc) {
+            println!("hit breakpoint at x{:04X}", pc);
+            return;
+        }
+    }
+}
+
+fn print_registers(vm: &Lc3Vm) {
+    for (i, value) in vm.registers[..8].iter().enumerate() {
+        print!("R{}=x{:04X}  ", i, value);
+    }
+    println!();
+    println!(
+        "PC=x{:04X}  COND=x{:04X}",
+        vm.register(Register::PC),
+        vm.register(Register::COND)
+    );
+}
+
+fn print_memory(vm: &Lc3Vm, addr: u16, count: u16) {
+    for i in 0..count {
+        let a = addr.wrapping_add(i);
+        let word = vm.read(a);
+        println!("x{:04X}: x{:04X}  {}", a, word, disassemble(word));
+    }
+}
+
+/// Parse an address argument: `x3000`, `0x3000`, or a bare decimal.
+fn parse_addr(token: &str) -> Option<u16> {
+    let t = token.trim();
+    if let Some(rest) = t.strip_prefix("0x").or_else(|| t.strip_prefix("0X")) {
+        u16::from_str_radix(rest, 16).ok()
+    } else if let Some(rest) = t.strip_prefix('x').or_else(|| t.strip_prefix('X')) {
+        u16::from_str_radix(rest, 16).ok()
+    } else {
+        t.parse().ok()
+    }
+}
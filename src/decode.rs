@@ -0,0 +1,130 @@
+use crate::sign_extend;
+
+/// The second source operand of `ADD`/`AND`: either a register or a
+/// sign-extended 5-bit immediate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddOperand {
+    Reg(u16),
+    Imm(i16),
+}
+
+/// A fully decoded LC-3 instruction, with every field pulled out of the raw
+/// 16-bit encoding so the execute stage no longer has to twiddle bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    Add { dr: u16, sr1: u16, operand: AddOperand },
+    And { dr: u16, sr1: u16, operand: AddOperand },
+    Not { dr: u16, sr: u16 },
+    Br { n: bool, z: bool, p: bool, offset: i16 },
+    Jmp { base: u16 },
+    Jsr { offset: i16 },
+    Jsrr { base: u16 },
+    Ld { dr: u16, offset: i16 },
+    Ldi { dr: u16, offset: i16 },
+    Ldr { dr: u16, base: u16, offset: i16 },
+    Lea { dr: u16, offset: i16 },
+    St { sr: u16, offset: i16 },
+    Sti { sr: u16, offset: i16 },
+    Str { sr: u16, base: u16, offset: i16 },
+    Trap { vect: u16 },
+    Rti,
+    Reserved(u16),
+}
+
+/// Sign-extend the low `bits` of `instr` and return it as a signed offset.
+fn offset(instr: u16, bits: u8) -> i16 {
+    sign_extend(instr & ((1 << bits) - 1), bits) as i16
+}
+
+fn add_operand(instr: u16) -> AddOperand {
+    if (instr >> 5) & 1 == 1 {
+        AddOperand::Imm(sign_extend(instr & 0x1F, 5) as i16)
+    } else {
+        AddOperand::Reg(instr & 0x7)
+    }
+}
+
+/// Decode a raw instruction word into its typed form.
+pub fn decode(instr: u16) -> Instruction {
+    let dr = (instr >> 9) & 0x7;
+    let sr1 = (instr >> 6) & 0x7;
+
+    match instr >> 12 {
+        0 => Instruction::Br {
+            n: (instr >> 11) & 1 == 1,
+            z: (instr >> 10) & 1 == 1,
+            p: (instr >> 9) & 1 == 1,
+            offset: offset(instr, 9),
+        },
+        1 => Instruction::Add { dr, sr1, operand: add_operand(instr) },
+        2 => Instruction::Ld { dr, offset: offset(instr, 9) },
+        3 => Instruction::St { sr: dr, offset: offset(instr, 9) },
+        4 => {
+            if (instr >> 11) & 1 == 1 {
+                Instruction::Jsr { offset: offset(instr, 11) }
+            } else {
+                Instruction::Jsrr { base: sr1 }
+            }
+        }
+        5 => Instruction::And { dr, sr1, operand: add_operand(instr) },
+        6 => Instruction::Ldr { dr, base: sr1, offset: offset(instr, 6) },
+        7 => Instruction::Str { sr: dr, base: sr1, offset: offset(instr, 6) },
+        8 => Instruction::Rti,
+        9 => Instruction::Not { dr, sr: sr1 },
+        10 => Instruction::Ldi { dr, offset: offset(instr, 9) },
+        11 => Instruction::Sti { sr: dr, offset: offset(instr, 9) },
+        12 => Instruction::Jmp { base: sr1 },
+        14 => Instruction::Lea { dr, offset: offset(instr, 9) },
+        15 => Instruction::Trap { vect: instr & 0xFF },
+        _ => Instruction::Reserved(instr),
+    }
+}
+
+/// Render a raw instruction word back to canonical LC-3 assembly, e.g.
+/// `ADD R1, R2, #5` or `BRnzp #-3`.
+pub fn disassemble(instr: u16) -> String {
+    fn alu(name: &str, dr: u16, sr1: u16, operand: AddOperand) -> String {
+        match operand {
+            AddOperand::Reg(sr2) => format!("{} R{}, R{}, R{}", name, dr, sr1, sr2),
+            AddOperand::Imm(imm) => format!("{} R{}, R{}, #{}", name, dr, sr1, imm),
+        }
+    }
+
+    match decode(instr) {
+        Instruction::Add { dr, sr1, operand } => alu("ADD", dr, sr1, operand),
+        Instruction::And { dr, sr1, operand } => alu("AND", dr, sr1, operand),
+        Instruction::Not { dr, sr } => format!("NOT R{}, R{}", dr, sr),
+        Instruction::Br { n, z, p, offset } => {
+            let mut suffix = String::new();
+            if n {
+                suffix.push('n');
+            }
+            if z {
+                suffix.push('z');
+            }
+            if p {
+                suffix.push('p');
+            }
+            format!("BR{} #{}", suffix, offset)
+        }
+        Instruction::Jmp { base } => {
+            if base == 7 {
+                "RET".to_string()
+            } else {
+                format!("JMP R{}", base)
+            }
+        }
+        Instruction::Jsr { offset } => format!("JSR #{}", offset),
+        Instruction::Jsrr { base } => format!("JSRR R{}", base),
+        Instruction::Ld { dr, offset } => format!("LD R{}, #{}", dr, offset),
+        Instruction::Ldi { dr, offset } => format!("LDI R{}, #{}", dr, offset),
+        Instruction::Ldr { dr, base, offset } => format!("LDR R{}, R{}, #{}", dr, base, offset),
+        Instruction::Lea { dr, offset } => format!("LEA R{}, #{}", dr, offset),
+        Instruction::St { sr, offset } => format!("ST R{}, #{}", sr, offset),
+        Instruction::Sti { sr, offset } => format!("STI R{}, #{}", sr, offset),
+        Instruction::Str { sr, base, offset } => format!("STR R{}, R{}, #{}", sr, base, offset),
+        Instruction::Trap { vect } => format!("TRAP x{:02X}", vect),
+        Instruction::Rti => "RTI".to_string(),
+        Instruction::Reserved(_) => format!(".FILL x{:04X}", instr),
+    }
+}
@@ -0,0 +1,174 @@
+use std::io::{BufReader, Read, Write};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Keyboard status register: bit 15 is "ready", bit 14 is "interrupt enable".
+pub const KBSR: u16 = 0xFE00;
+/// Keyboard data register: the last character typed.
+pub const KBDR: u16 = 0xFE02;
+/// Display status register: bit 15 is "ready" (always, for our display).
+pub const DSR: u16 = 0xFE04;
+/// Display data register: writing a word prints its low byte.
+pub const DDR: u16 = 0xFE06;
+/// Machine control register: bit 15 enables the clock.
+pub const MCR: u16 = 0xFFFE;
+
+/// Base of the interrupt vector table.
+pub const INTERRUPT_TABLE: u16 = 0x0100;
+/// Interrupt vector and priority of the keyboard.
+pub const KEYBOARD_VECTOR: u16 = 0x80;
+pub const KEYBOARD_PRIORITY: u16 = 4;
+/// Interrupt vector and priority of the countdown timer.
+pub const TIMER_VECTOR: u16 = 0x81;
+pub const TIMER_PRIORITY: u16 = 4;
+
+/// Number of instructions between timer interrupts.
+const CLOCK_PERIOD: u16 = 40000;
+/// The supervisor stack grows down from here when an interrupt is serviced.
+const SUPERVISOR_STACK: u16 = 0x3000;
+
+const READY: u16 = 1 << 15;
+const INTERRUPT_ENABLE: u16 = 1 << 14;
+
+/// Peripheral and interrupt state that lives outside the plain RAM array: the
+/// memory-mapped device registers, a decrementing timer, and the bookkeeping
+/// needed to switch between user and supervisor contexts on an interrupt.
+pub struct Devices {
+    kbsr: u16,
+    kbdr: u16,
+    mcr: u16,
+    clock: u16,
+    keyboard: Receiver<u8>,
+    /// `true` while executing in user mode (PSR bit 15 set).
+    pub user_mode: bool,
+    /// Current processor priority level (PSR bits 10:8).
+    pub priority: u16,
+    /// Saved user and supervisor stack pointers (R6) across a mode switch.
+    pub saved_usp: u16,
+    pub saved_ssp: u16,
+}
+
+impl Devices {
+    pub fn new() -> Self {
+        // A reader thread funnels stdin bytes through a channel so the VM can
+        // poll the keyboard without blocking the fetch/execute loop.
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for byte in BufReader::new(std::io::stdin()).bytes() {
+                match byte {
+                    Ok(b) => {
+                        if tx.send(b).is_err() {
+                            break;
+                        }
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+
+        Devices::with_keyboard(rx)
+    }
+
+    /// Build a device layer with no keyboard reader thread. The interactive
+    /// debugger reads stdin itself line-by-line; spawning the reader too would
+    /// race it for the same file descriptor and swallow typed commands.
+    pub fn headless() -> Self {
+        // A live receiver whose sender is dropped: `try_recv`/`recv` simply
+        // report the keyboard as empty, so no stdin is consumed here.
+        let (_tx, rx) = mpsc::channel();
+        Devices::with_keyboard(rx)
+    }
+
+    fn with_keyboard(keyboard: Receiver<u8>) -> Self {
+        Devices {
+            kbsr: 0,
+            kbdr: 0,
+            mcr: READY,
+            clock: CLOCK_PERIOD,
+            keyboard,
+            user_mode: true,
+            priority: 0,
+            saved_usp: 0,
+            saved_ssp: SUPERVISOR_STACK,
+        }
+    }
+
+    /// Intercept a load from a device register, returning `None` for a plain
+    /// memory address.
+    pub fn read(&mut self, addr: u16) -> Option<u16> {
+        match addr {
+            KBSR => Some(self.kbsr),
+            KBDR => {
+                // Reading the data register clears the ready bit.
+                self.kbsr &= !READY;
+                Some(self.kbdr)
+            }
+            DSR => Some(READY),
+            DDR => Some(0),
+            MCR => Some(self.mcr),
+            _ => None,
+        }
+    }
+
+    /// Intercept a store to a device register, returning `None` for a plain
+    /// memory address.
+    pub fn write(&mut self, addr: u16, value: u16) -> Option<()> {
+        match addr {
+            KBSR => self.kbsr = value,
+            KBDR => {}
+            DSR => {}
+            DDR => {
+                print!("{}", (value & 0xFF) as u8 as char);
+                std::io::stdout().flush().ok();
+            }
+            MCR => self.mcr = value,
+            _ => return None,
+        }
+        Some(())
+    }
+
+    /// Latch a waiting keystroke into KBDR and raise the ready bit.
+    pub fn poll_keyboard(&mut self) {
+        if self.kbsr & READY == 0 {
+            if let Ok(byte) = self.keyboard.try_recv() {
+                self.kbdr = byte as u16;
+                self.kbsr |= READY;
+            }
+        }
+    }
+
+    /// Read a character for a `GETC`/`IN` trap, blocking until one arrives.
+    pub fn getc(&mut self) -> u16 {
+        if self.kbsr & READY != 0 {
+            self.kbsr &= !READY;
+            self.kbdr
+        } else {
+            self.keyboard.recv().map(|b| b as u16).unwrap_or(0)
+        }
+    }
+
+    /// Advance the clock by one instruction, returning `true` on expiry.
+    pub fn tick(&mut self) -> bool {
+        if self.mcr & READY == 0 {
+            return false;
+        }
+        if self.clock == 0 {
+            self.clock = CLOCK_PERIOD;
+            true
+        } else {
+            self.clock -= 1;
+            false
+        }
+    }
+
+    /// Is a keyboard interrupt pending (ready and interrupt-enabled)?
+    pub fn keyboard_interrupt_pending(&self) -> bool {
+        self.kbsr & (READY | INTERRUPT_ENABLE) == (READY | INTERRUPT_ENABLE)
+    }
+}
+
+impl Default for Devices {
+    fn default() -> Self {
+        Self::new()
+    }
+}
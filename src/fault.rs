@@ -0,0 +1,32 @@
+use std::fmt;
+
+/// A recoverable fault raised by the VM. Faults are returned to the caller so
+/// an embedder can report or recover from them rather than having the process
+/// aborted mid-instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// A reserved opcode (`RES`) was fetched, or the privileged `RTI` was
+    /// executed in user mode.
+    IllegalOpcode,
+    /// A `TRAP` with an unrecognized trap vector was executed.
+    BadTrap,
+    /// A load or store addressed memory outside the machine's address space.
+    ///
+    /// With the address space sized to the full `1 << 16` and every effective
+    /// address a wrapping `u16`, this cannot fire for [`crate::vm::Lc3Vm`]; the
+    /// variant is kept so embedders backing the VM with a smaller slice still
+    /// have a fault to surface.
+    MemoryAccess { addr: u16 },
+}
+
+impl fmt::Display for Fault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fault::IllegalOpcode => write!(f, "illegal opcode"),
+            Fault::BadTrap => write!(f, "bad trap vector"),
+            Fault::MemoryAccess { addr } => write!(f, "memory access fault at x{:04X}", addr),
+        }
+    }
+}
+
+impl std::error::Error for Fault {}
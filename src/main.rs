@@ -1,8 +1,25 @@
 use std::io::Read;
 
+mod assembler;
+mod debugger;
+mod decode;
+mod device;
+mod fault;
+mod vm;
+
+use fault::Fault;
+use vm::Lc3Vm;
+
+/// Size of the LC-3 address space: the full 2^16 words.
+const MEMORY_SIZE: usize = 1 << 16;
+
+/// PSR bit 15: set while the processor is in user (unprivileged) mode.
+const PRIVILEGE: u16 = 1 << 15;
+
 #[repr(C)]
 #[derive(Clone, Copy)]
 #[allow(dead_code)]
+#[allow(clippy::upper_case_acronyms)]
 enum Register {
     R0 = 0,
     R1,
@@ -19,6 +36,7 @@ enum Register {
 
 #[repr(C)]
 #[derive(Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
 enum Opcode {
     BRANCH = 0,
     ADD,
@@ -64,6 +82,7 @@ impl From<u16> for Opcode {
 
 #[repr(C)]
 #[derive(Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
 enum Flag {
     POSITIVE = 1 << 0,
     ZERO = 1 << 1,
@@ -72,6 +91,7 @@ enum Flag {
 
 #[repr(C)]
 #[derive(Clone, Copy)]
+#[allow(clippy::upper_case_acronyms)]
 enum Trap {
     GETC = 0x20,    // get character from keyboard
     OUT = 0x21,     // output a character
@@ -81,20 +101,53 @@ enum Trap {
     HALT = 0x25,    // halt the program
 }
 
-impl From<u16> for Trap {
-    fn from(trap: u16) -> Self {
+impl TryFrom<u16> for Trap {
+    type Error = Fault;
+
+    fn try_from(trap: u16) -> Result<Self, Self::Error> {
         match trap {
-            0x20 => Trap::GETC,
-            0x21 => Trap::OUT,
-            0x22 => Trap::PUTS,
-            0x23 => Trap::IN,
-            0x24 => Trap::PUTSP,
-            0x25 => Trap::HALT,
-            _ => panic!("Invalid trap code: {}", trap),
+            0x20 => Ok(Trap::GETC),
+            0x21 => Ok(Trap::OUT),
+            0x22 => Ok(Trap::PUTS),
+            0x23 => Ok(Trap::IN),
+            0x24 => Ok(Trap::PUTSP),
+            0x25 => Ok(Trap::HALT),
+            _ => Err(Fault::BadTrap),
         }
     }
 }
 
+fn load_image(path: &str, memory: &mut [u16]) -> std::io::Result<u16> {
+    use std::io::{Error, ErrorKind};
+
+    let mut file = std::fs::File::open(path)?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+
+    // The first big-endian word is the origin, the address at which the
+    // remaining words are loaded.
+    if bytes.len() < 2 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "object file is too short to contain an origin word",
+        ));
+    }
+    let origin = u16::from_be_bytes([bytes[0], bytes[1]]);
+    for (i, word) in bytes[2..].chunks_exact(2).enumerate() {
+        match memory.get_mut(origin as usize + i) {
+            Some(slot) => *slot = u16::from_be_bytes([word[0], word[1]]),
+            None => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    "object image extends past the end of the address space",
+                ));
+            }
+        }
+    }
+
+    Ok(origin)
+}
+
 fn sign_extend(x: u16, bit_count: u8) -> u16 {
     if (x >> (bit_count - 1)) & 1 == 1{
         x | (0xFFFF << bit_count)
@@ -109,157 +162,62 @@ fn get_flag(r: u16) -> Flag {
     else { Flag::POSITIVE }
 }
 
-fn update_flag(registers: &mut [u16; Register::COUNT as usize], r: u16) {
-    registers[Register::COND as usize] = get_flag(registers[r as usize]) as u16;
-}
-
 fn main() {
-    let mut memory = [0u16; 1 << 16 - 1];
-    let mut registers = [0u16; Register::COUNT as usize];
-
-    let mut running = true;
-    while running {
-        let instr = memory[registers[Register::PC as usize] as usize];
-        let op = instr >> 12;
-
-        match op.into() {
-            Opcode::ADD => {
-                let r0 = (instr >> 9) & 0x7;
-                let r1 = (instr >> 6) & 0x7;
-                let imm_flag = (instr >> 5) & 0x1;
-
-                if imm_flag == 1 {
-                    let imm5 = sign_extend(instr & 0x1F, 5);
-                    registers[r0 as usize] = registers[r1 as usize] + imm5;
-                } else {
-                    let r2 = instr & 0x7;
-                    registers[r0 as usize] = registers[r1 as usize] + registers[r2 as usize];
-                }
-
-                update_flag(&mut registers, r0);
-            },
-            Opcode::AND => {
-                let r0 = (instr >> 9) & 0x7;
-                let r1 = (instr >> 6) & 0x7;
-                let imm_flag = (instr >> 5) & 0x1;
-
-                if imm_flag == 1 {
-                    let imm5 = sign_extend(instr & 0x1F, 5);
-                    registers[r0 as usize] = registers[r1 as usize] & imm5;
-                } else {
-                    let r2 = instr & 0x7;
-                    registers[r0 as usize] = registers[r1 as usize] & registers[r2 as usize];
-                }
+    let args: Vec<String> = std::env::args().collect();
+
+    // `lc3 asm <input.asm> <output.obj>` runs the bundled assembler instead of
+    // the VM, producing an image the loader can later execute.
+    if args.get(1).map(String::as_str) == Some("asm") {
+        let input = args.get(2).expect("usage: lc3 asm <input.asm> <output.obj>");
+        let output = args.get(3).expect("usage: lc3 asm <input.asm> <output.obj>");
+        let source = std::fs::read_to_string(input)
+            .unwrap_or_else(|e| panic!("failed to read {}: {}", input, e));
+        let image = assembler::assemble(&source).unwrap_or_else(|e| panic!("{}", e));
+        std::fs::write(output, image)
+            .unwrap_or_else(|e| panic!("failed to write {}: {}", output, e));
+        return;
+    }
 
-                update_flag(&mut registers, r0);
-            },
-            Opcode::NOT => {
-                let r0 = (instr >> 9) & 0x7;
-                let r1 = (instr >> 6) & 0x7;
+    // `lc3 dis <image.obj>` dumps a loaded image as readable assembly.
+    if args.get(1).map(String::as_str) == Some("dis") {
+        let mut image = [0u16; MEMORY_SIZE];
+        let path = args.get(2).expect("usage: lc3 dis <image.obj>");
+        let origin = load_image(path, &mut image)
+            .unwrap_or_else(|e| panic!("failed to load image {}: {}", path, e));
+        let end = image[origin as usize..]
+            .iter()
+            .rposition(|&w| w != 0)
+            .map_or(origin as usize, |i| origin as usize + i + 1);
+        for (offset, &word) in image[origin as usize..end].iter().enumerate() {
+            println!("x{:04X}  {}", origin as usize + offset, decode::disassemble(word));
+        }
+        return;
+    }
 
-                registers[r0 as usize] = !registers[r1 as usize];
+    // `lc3 debug <image.obj> [...]` loads the images and drops into the
+    // interactive debugger instead of running to completion.
+    let debug = args.get(1).map(String::as_str) == Some("debug");
+    let images: Vec<&String> = if debug {
+        args.iter().skip(2).collect()
+    } else {
+        args.iter().skip(1).collect()
+    };
+
+    // Debug mode reads stdin for its own command prompt, so it uses a VM
+    // without the background keyboard reader that would otherwise race it.
+    let mut vm = if debug { Lc3Vm::headless() } else { Lc3Vm::new() };
+
+    // Load each image given on the command line. Later images (e.g. a user
+    // program) are loaded over earlier ones (e.g. an OS trap-vector image),
+    // and execution begins at the origin of the last image loaded.
+    for path in images {
+        vm.load_image(path)
+            .unwrap_or_else(|e| panic!("failed to load image {}: {}", path, e));
+    }
 
-                update_flag(&mut registers, r0);
-            },
-            Opcode::BRANCH => {
-                let pc_offset = sign_extend(instr & 0x1FF, 9);
-                if ((instr >> 9) & registers[Register::COND as usize] & 0x7) != 0 {
-                    registers[Register::PC as usize] += pc_offset;
-                }
-            },
-            Opcode::JUMP => {
-                let r0 = (instr >> 6) & 0x7;
-                registers[Register::PC as usize] = registers[r0 as usize];
-            },
-            Opcode::JUMPR => {
-                registers[Register::R7 as usize] = registers[Register::PC as usize];
-                let pc_offset = sign_extend(instr & 0x7FF, 11);
-                if ((instr >> 11) & 1) != 0 {
-                    registers[Register::PC as usize] += pc_offset;
-                } else {
-                    let r0 = (instr >> 6) & 0x7;
-                    registers[Register::PC as usize] = registers[r0 as usize];
-                }
-            },
-            Opcode::LOAD => {
-                let r0 = (instr >> 9) & 0x7;
-                let pc_offset = sign_extend(instr & 0x1FF, 9);
-                registers[r0 as usize] = memory[registers[Register::PC as usize] as usize + pc_offset as usize];
-                update_flag(&mut registers, r0);
-            },
-            Opcode::LOADI => {
-                let r0 = (instr >> 9) & 0x7;
-                let pc_offset = sign_extend(instr & 0x1FF, 9);
-                registers[r0 as usize] = memory[memory[registers[Register::PC as usize] as usize + pc_offset as usize] as usize];
-                update_flag(&mut registers, r0);
-            },
-            Opcode::LOADR => {
-                let r0 = (instr >> 9) & 0x7;
-                let base = (instr >> 6) & 0x7;
-                let offset = sign_extend(instr & 0x3F, 6);
-                registers[r0 as usize] = memory[registers[base as usize] as usize + offset as usize];
-                update_flag(&mut registers, r0);
-            },
-            Opcode::LEA => {
-                let r0 = (instr >> 9) & 0x7;
-                let pc_offset = sign_extend(instr & 0x1FF, 9);
-                registers[r0 as usize] = registers[Register::PC as usize] + pc_offset;
-                update_flag(&mut registers, r0);
-            },
-            Opcode::STORE => {
-                let r0 = (instr >> 9) & 0x7;
-                let pc_offset = sign_extend(instr & 0x1FF, 9);
-                memory[registers[Register::PC as usize] as usize + pc_offset as usize] = registers[r0 as usize];
-            },
-            Opcode::STOREI => {
-                let r0 = (instr >> 9) & 0x7;
-                let pc_offset = sign_extend(instr & 0x1FF, 9);
-                memory[memory[registers[Register::PC as usize] as usize + pc_offset as usize] as usize] = registers[r0 as usize];
-            },
-            Opcode::STORER => {
-                let r0 = (instr >> 9) & 0x7;
-                let base = (instr >> 6) & 0x7;
-                let offset = sign_extend(instr & 0x3F, 6);
-                memory[registers[base as usize] as usize + offset as usize] = registers[r0 as usize];
-            },
-            Opcode::TRAP => {
-                match (instr & 0xFF).into() {
-                    Trap::GETC => {
-                        registers[Register::R0 as usize] = std::io::stdin().bytes().next().unwrap().unwrap() as u16;
-                    },
-                    Trap::OUT => {
-                        print!("{}", registers[Register::R0 as usize] as u8 as char);
-                    },
-                    Trap::PUTS => {
-                        let mut i = registers[Register::R0 as usize] as usize;
-                        while memory[i] != 0 {
-                            print!("{}", memory[i] as u8 as char);
-                            i += 1;
-                        }
-                    },
-                    Trap::IN => {
-                        print!("Enter a character: ");
-                        registers[Register::R0 as usize] = std::io::stdin().bytes().next().unwrap().unwrap() as u16;
-                    },
-                    Trap::PUTSP => {
-                        let mut i = registers[Register::R0 as usize] as usize;
-                        while memory[i] != 0 {
-                            print!("{}", (memory[i] & 0xFF) as u8 as char);
-                            let ch = (memory[i] >> 8) as u8;
-                            if ch != 0 {
-                                print!("{}", ch as char);
-                            }
-                            i += 1;
-                        }
-                    },
-                    Trap::HALT => {
-                        println!("HALT");
-                        running = false;
-                    },
-                }
-            },
-            Opcode::RTI | Opcode::RES => { panic!("Illegal Opcode {}", op) },
-        }
+    if debug {
+        debugger::debug(&mut vm);
+    } else if let Err(fault) = vm.run() {
+        eprintln!("VM fault: {}", fault);
     }
-    println!("Hello, world!");
 }
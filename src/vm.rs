@@ -0,0 +1,376 @@
+use crate::decode::{self, AddOperand, Instruction};
+use crate::device::{self, Devices};
+use crate::fault::Fault;
+use crate::{get_flag, load_image, Register, Trap, MEMORY_SIZE, PRIVILEGE};
+
+/// A self-contained LC-3 virtual machine: the address space, the register
+/// file, and the memory-mapped device/interrupt state. Embedders drive it one
+/// instruction at a time with [`Lc3Vm::step`] or to completion with
+/// [`Lc3Vm::run`].
+pub struct Lc3Vm {
+    pub memory: [u16; MEMORY_SIZE],
+    pub registers: [u16; Register::COUNT as usize],
+    pub running: bool,
+    devices: Devices,
+}
+
+impl Lc3Vm {
+    pub fn new() -> Self {
+        Lc3Vm::with_devices(Devices::new())
+    }
+
+    /// Build a VM with no keyboard reader thread, for the interactive debugger
+    /// which consumes stdin itself (see [`Devices::headless`]).
+    pub fn headless() -> Self {
+        Lc3Vm::with_devices(Devices::headless())
+    }
+
+    fn with_devices(devices: Devices) -> Self {
+        Lc3Vm {
+            memory: [0; MEMORY_SIZE],
+            registers: [0; Register::COUNT as usize],
+            running: true,
+            devices,
+        }
+    }
+
+    /// Load an object image into memory and set `PC` to its origin.
+    pub fn load_image(&mut self, path: &str) -> std::io::Result<u16> {
+        let origin = load_image(path, &mut self.memory)?;
+        self.registers[Register::PC as usize] = origin;
+        Ok(origin)
+    }
+
+    /// Read a register.
+    pub fn register(&self, r: Register) -> u16 {
+        self.registers[r as usize]
+    }
+
+    /// Write a register.
+    #[allow(dead_code)]
+    pub fn set_register(&mut self, r: Register, value: u16) {
+        self.registers[r as usize] = value;
+    }
+
+    /// Inspect a memory word without triggering device side effects.
+    pub fn read(&self, addr: u16) -> u16 {
+        self.memory[addr as usize]
+    }
+
+    /// Write a memory word directly, bypassing the device layer.
+    #[allow(dead_code)]
+    pub fn write(&mut self, addr: u16, value: u16) {
+        self.memory[addr as usize] = value;
+    }
+
+    /// Run until the machine halts or a fault is raised, polling devices and
+    /// the clock between instructions.
+    pub fn run(&mut self) -> Result<(), Fault> {
+        while self.running {
+            self.check_interrupts()?;
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    fn mem_read(&mut self, addr: u16) -> Result<u16, Fault> {
+        if let Some(value) = self.devices.read(addr) {
+            return Ok(value);
+        }
+        self.memory
+            .get(addr as usize)
+            .copied()
+            .ok_or(Fault::MemoryAccess { addr })
+    }
+
+    fn mem_write(&mut self, addr: u16, value: u16) -> Result<(), Fault> {
+        if self.devices.write(addr, value).is_some() {
+            return Ok(());
+        }
+        match self.memory.get_mut(addr as usize) {
+            Some(slot) => {
+                *slot = value;
+                Ok(())
+            }
+            None => Err(Fault::MemoryAccess { addr }),
+        }
+    }
+
+    fn update_flag(&mut self, r: u16) {
+        self.registers[Register::COND as usize] =
+            get_flag(self.registers[r as usize]) as u16;
+    }
+
+    /// Assemble the current PSR from the privilege bit, priority, and
+    /// condition codes.
+    fn compose_psr(&self) -> u16 {
+        let privilege = if self.devices.user_mode { PRIVILEGE } else { 0 };
+        privilege | (self.devices.priority << 8) | (self.registers[Register::COND as usize] & 0x7)
+    }
+
+    /// Restore processor state from a PSR popped off the supervisor stack.
+    fn apply_psr(&mut self, psr: u16) {
+        self.devices.user_mode = psr & PRIVILEGE != 0;
+        self.devices.priority = (psr >> 8) & 0x7;
+        self.registers[Register::COND as usize] = psr & 0x7;
+    }
+
+    /// Enter an interrupt service routine: switch to the supervisor stack,
+    /// push the interrupted PSR and PC, and vector to the handler.
+    fn raise_interrupt(&mut self, vector: u16, priority: u16) -> Result<(), Fault> {
+        let psr = self.compose_psr();
+
+        // Interrupting user code swaps in the supervisor stack pointer.
+        if self.devices.user_mode {
+            self.devices.saved_usp = self.registers[Register::R6 as usize];
+            self.registers[Register::R6 as usize] = self.devices.saved_ssp;
+        }
+        self.devices.user_mode = false;
+        self.devices.priority = priority;
+
+        let sp = self.registers[Register::R6 as usize].wrapping_sub(1);
+        self.registers[Register::R6 as usize] = sp;
+        self.mem_write(sp, psr)?;
+        let sp = self.registers[Register::R6 as usize].wrapping_sub(1);
+        self.registers[Register::R6 as usize] = sp;
+        let pc = self.registers[Register::PC as usize];
+        self.mem_write(sp, pc)?;
+
+        self.registers[Register::PC as usize] =
+            self.mem_read(device::INTERRUPT_TABLE.wrapping_add(vector))?;
+        Ok(())
+    }
+
+    /// Is a handler installed at `vector`'s interrupt-table entry? The table is
+    /// plain RAM, so a zero entry means no service routine was loaded.
+    fn vector_installed(&self, vector: u16) -> bool {
+        let entry = device::INTERRUPT_TABLE.wrapping_add(vector);
+        self.memory[entry as usize] != 0
+    }
+
+    /// Poll devices and the clock between instructions, vectoring to a handler
+    /// if an enabled interrupt of sufficient priority is pending.
+    pub fn check_interrupts(&mut self) -> Result<(), Fault> {
+        self.devices.poll_keyboard();
+        let timer_expired = self.devices.tick();
+
+        // Only vector when a handler is actually installed at the device's
+        // interrupt-table entry. A bare program (no OS) leaves the table zeroed,
+        // so this keeps an uninterrupted fetch/execute loop running to baseline
+        // behaviour instead of jumping through an empty vector to x0000.
+        if self.devices.keyboard_interrupt_pending()
+            && self.devices.priority < device::KEYBOARD_PRIORITY
+            && self.vector_installed(device::KEYBOARD_VECTOR)
+        {
+            return self.raise_interrupt(device::KEYBOARD_VECTOR, device::KEYBOARD_PRIORITY);
+        }
+        if timer_expired
+            && self.devices.priority < device::TIMER_PRIORITY
+            && self.vector_installed(device::TIMER_VECTOR)
+        {
+            return self.raise_interrupt(device::TIMER_VECTOR, device::TIMER_PRIORITY);
+        }
+        Ok(())
+    }
+
+    /// Fetch, decode, and execute the instruction at `PC`. Effective addresses
+    /// wrap modulo 2^16, as the LC-3 defines, and any fault is returned to the
+    /// caller rather than aborting the process.
+    pub fn step(&mut self) -> Result<(), Fault> {
+        let pc = self.registers[Register::PC as usize];
+        let instr = self.mem_read(pc)?;
+        // Advance PC past the fetched word before executing. PC-relative
+        // offsets and the `JSR`/`JSRR` return address are computed against this
+        // incremented value, matching the assembler's encoding.
+        let pc = pc.wrapping_add(1);
+        self.registers[Register::PC as usize] = pc;
+
+        match decode::decode(instr) {
+            Instruction::Add { dr, sr1, operand } => {
+                self.registers[dr as usize] = match operand {
+                    AddOperand::Imm(imm) => self.registers[sr1 as usize].wrapping_add(imm as u16),
+                    AddOperand::Reg(sr2) => {
+                        self.registers[sr1 as usize].wrapping_add(self.registers[sr2 as usize])
+                    }
+                };
+                self.update_flag(dr);
+            }
+            Instruction::And { dr, sr1, operand } => {
+                self.registers[dr as usize] = match operand {
+                    AddOperand::Imm(imm) => self.registers[sr1 as usize] & imm as u16,
+                    AddOperand::Reg(sr2) => self.registers[sr1 as usize] & self.registers[sr2 as usize],
+                };
+                self.update_flag(dr);
+            }
+            Instruction::Not { dr, sr } => {
+                self.registers[dr as usize] = !self.registers[sr as usize];
+                self.update_flag(dr);
+            }
+            Instruction::Br { n, z, p, offset } => {
+                let flags = ((n as u16) << 2) | ((z as u16) << 1) | p as u16;
+                if (flags & self.registers[Register::COND as usize] & 0x7) != 0 {
+                    self.registers[Register::PC as usize] = pc.wrapping_add(offset as u16);
+                }
+            }
+            Instruction::Jmp { base } => {
+                self.registers[Register::PC as usize] = self.registers[base as usize];
+            }
+            Instruction::Jsr { offset } => {
+                self.registers[Register::R7 as usize] = pc;
+                self.registers[Register::PC as usize] = pc.wrapping_add(offset as u16);
+            }
+            Instruction::Jsrr { base } => {
+                self.registers[Register::R7 as usize] = pc;
+                self.registers[Register::PC as usize] = self.registers[base as usize];
+            }
+            Instruction::Ld { dr, offset } => {
+                self.registers[dr as usize] = self.mem_read(pc.wrapping_add(offset as u16))?;
+                self.update_flag(dr);
+            }
+            Instruction::Ldi { dr, offset } => {
+                let indirect = self.mem_read(pc.wrapping_add(offset as u16))?;
+                self.registers[dr as usize] = self.mem_read(indirect)?;
+                self.update_flag(dr);
+            }
+            Instruction::Ldr { dr, base, offset } => {
+                let addr = self.registers[base as usize].wrapping_add(offset as u16);
+                self.registers[dr as usize] = self.mem_read(addr)?;
+                self.update_flag(dr);
+            }
+            Instruction::Lea { dr, offset } => {
+                self.registers[dr as usize] = pc.wrapping_add(offset as u16);
+                self.update_flag(dr);
+            }
+            Instruction::St { sr, offset } => {
+                let value = self.registers[sr as usize];
+                self.mem_write(pc.wrapping_add(offset as u16), value)?;
+            }
+            Instruction::Sti { sr, offset } => {
+                let indirect = self.mem_read(pc.wrapping_add(offset as u16))?;
+                let value = self.registers[sr as usize];
+                self.mem_write(indirect, value)?;
+            }
+            Instruction::Str { sr, base, offset } => {
+                let addr = self.registers[base as usize].wrapping_add(offset as u16);
+                let value = self.registers[sr as usize];
+                self.mem_write(addr, value)?;
+            }
+            Instruction::Trap { vect } => match Trap::try_from(vect)? {
+                Trap::GETC => {
+                    self.registers[Register::R0 as usize] = self.devices.getc();
+                }
+                Trap::OUT => {
+                    print!("{}", self.registers[Register::R0 as usize] as u8 as char);
+                }
+                Trap::PUTS => {
+                    let mut addr = self.registers[Register::R0 as usize];
+                    loop {
+                        let c = self.mem_read(addr)?;
+                        if c == 0 {
+                            break;
+                        }
+                        print!("{}", c as u8 as char);
+                        addr = addr.wrapping_add(1);
+                    }
+                }
+                Trap::IN => {
+                    print!("Enter a character: ");
+                    self.registers[Register::R0 as usize] = self.devices.getc();
+                }
+                Trap::PUTSP => {
+                    let mut addr = self.registers[Register::R0 as usize];
+                    loop {
+                        let word = self.mem_read(addr)?;
+                        if word == 0 {
+                            break;
+                        }
+                        print!("{}", (word & 0xFF) as u8 as char);
+                        let ch = (word >> 8) as u8;
+                        if ch != 0 {
+                            print!("{}", ch as char);
+                        }
+                        addr = addr.wrapping_add(1);
+                    }
+                }
+                Trap::HALT => {
+                    println!("HALT");
+                    self.running = false;
+                }
+            },
+            Instruction::Rti => {
+                // `RTI` is privileged; executing it in user mode is illegal.
+                if self.devices.user_mode {
+                    return Err(Fault::IllegalOpcode);
+                }
+                let sp = self.registers[Register::R6 as usize];
+                let return_pc = self.mem_read(sp)?;
+                self.registers[Register::R6 as usize] = sp.wrapping_add(1);
+                let sp = self.registers[Register::R6 as usize];
+                let psr = self.mem_read(sp)?;
+                self.registers[Register::R6 as usize] = sp.wrapping_add(1);
+                self.registers[Register::PC as usize] = return_pc;
+                self.apply_psr(psr);
+                // Returning to user mode restores the user stack pointer.
+                if self.devices.user_mode {
+                    self.devices.saved_ssp = self.registers[Register::R6 as usize];
+                    self.registers[Register::R6 as usize] = self.devices.saved_usp;
+                }
+            }
+            Instruction::Reserved(_) => return Err(Fault::IllegalOpcode),
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Lc3Vm {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vm_at(origin: u16) -> Lc3Vm {
+        let mut vm = Lc3Vm::new();
+        vm.set_register(Register::PC, origin);
+        vm
+    }
+
+    #[test]
+    fn add_immediate_updates_register_and_advances_pc() {
+        let mut vm = vm_at(0x3000);
+        vm.write(0x3000, 0x1021); // ADD R0, R0, #1
+        vm.step().unwrap();
+        assert_eq!(vm.register(Register::R0), 1);
+        assert_eq!(vm.register(Register::PC), 0x3001);
+    }
+
+    #[test]
+    fn lea_computes_against_incremented_pc() {
+        let mut vm = vm_at(0x3000);
+        vm.write(0x3000, 0xE002); // LEA R0, #2
+        vm.step().unwrap();
+        // R0 = (PC after fetch) + 2 = 0x3001 + 2.
+        assert_eq!(vm.register(Register::R0), 0x3003);
+    }
+
+    #[test]
+    fn store_is_pc_relative() {
+        let mut vm = vm_at(0x3000);
+        vm.set_register(Register::R0, 0x1234);
+        vm.write(0x3000, 0x3000); // ST R0, #0
+        vm.step().unwrap();
+        assert_eq!(vm.read(0x3001), 0x1234);
+    }
+
+    #[test]
+    fn halt_trap_stops_the_machine() {
+        let mut vm = vm_at(0x3000);
+        vm.write(0x3000, 0xF025); // TRAP HALT
+        vm.step().unwrap();
+        assert!(!vm.running);
+    }
+}